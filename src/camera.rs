@@ -0,0 +1,95 @@
+extern crate nalgebra_glm as glm;
+
+use glm::{Mat4, Vec3};
+
+/// Interactive free-fly camera driven by the render viewport.
+///
+/// The controller lives on the UI thread: it owns the camera state, consumes
+/// keyboard and mouse input coming from the `PaneType::Render` pane and turns
+/// it into a view-projection matrix that is streamed to the render thread.
+/// This follows the split used in the wgpu threading tutorial, where the
+/// interactive state stays on the UI side and only the resulting matrix
+/// crosses the channel.
+pub struct CameraController {
+    position: Vec3,
+    yaw: f32,
+    pitch: f32,
+    speed: f32,
+    fov: f32,
+    near: f32,
+    far: f32,
+}
+
+impl CameraController {
+    pub fn new() -> Self {
+        Self {
+            position: Vec3::new(0.0f32, 0.0f32, 3.0f32),
+            // Face back toward the origin so a freshly loaded, origin-centred
+            // mesh is in view without the user having to turn around first.
+            yaw: std::f32::consts::PI,
+            pitch: 0.0f32,
+            speed: 3.0f32,
+            fov: 45.0f32.to_radians(),
+            near: 0.1f32,
+            far: 1000.0f32,
+        }
+    }
+
+    /// Advance the camera by one frame of input and return the new
+    /// view-projection matrix.
+    ///
+    /// `drag` is the mouse-drag delta accumulated on the render pane this
+    /// frame, `aspect` the pane's width/height ratio and `dt` the frame time
+    /// in seconds used to make movement frame-rate independent.
+    pub fn update(
+        &mut self,
+        input: &egui::InputState,
+        drag: egui::Vec2,
+        aspect: f32,
+        dt: f32,
+    ) -> Mat4 {
+        // Mouse look: accumulate the drag delta into yaw/pitch and clamp the
+        // pitch to ±89° so the view never flips over the poles.
+        let sensitivity = 0.005f32;
+        self.yaw -= drag.x * sensitivity;
+        self.pitch -= drag.y * sensitivity;
+        let pitch_limit = 89.0f32.to_radians();
+        self.pitch = self.pitch.clamp(-pitch_limit, pitch_limit);
+
+        let forward = Vec3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        );
+        let up = Vec3::new(0.0f32, 1.0f32, 0.0f32);
+        let right = glm::normalize(&glm::cross(&forward, &up));
+
+        // Poll held keys and move along the camera basis scaled by delta time.
+        let mut velocity = Vec3::zeros();
+        if input.key_down(egui::Key::W) {
+            velocity += forward;
+        }
+        if input.key_down(egui::Key::S) {
+            velocity -= forward;
+        }
+        if input.key_down(egui::Key::D) {
+            velocity += right;
+        }
+        if input.key_down(egui::Key::A) {
+            velocity -= right;
+        }
+        if input.key_down(egui::Key::E) {
+            velocity += up;
+        }
+        if input.key_down(egui::Key::Q) {
+            velocity -= up;
+        }
+        if velocity != Vec3::zeros() {
+            self.position += glm::normalize(&velocity) * self.speed * dt;
+        }
+
+        let view = glm::look_at(&self.position, &(self.position + forward), &up);
+        let proj = glm::perspective(aspect, self.fov, self.near, self.far);
+        proj * view
+    }
+}
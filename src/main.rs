@@ -2,26 +2,138 @@
 
 extern crate nalgebra_glm as glm;
 
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 use tracing::debug;
 
 use eframe::egui_wgpu::{self, wgpu};
-use egui::InputState;
 use glm::Mat4;
 
 use crossbeam_channel::{Receiver, Sender};
 
+use camera::CameraController;
 use render::Color;
 use render::PathTracerRenderContext;
+use scene::Scene;
 
+mod camera;
 mod render;
+mod scene;
+
+/// Render settings shared between the UI thread and the path-tracing thread.
+#[derive(Clone)]
+pub struct Settings {
+    /// Background / debug fill color used by the tracer stub.
+    pub color: [f32; 3],
+    /// Linear exposure multiplier applied before tone-mapping in the blit.
+    pub exposure: f32,
+    /// Tone-mapping operator: 0 = Reinhard, 1 = ACES filmic.
+    pub tonemap: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            color: [0.2f32, 0.4f32, 0.8f32],
+            exposure: 1.0f32,
+            tonemap: 1,
+        }
+    }
+}
 
 struct RenderView {}
 
+/// Reinhard tone-map: `c / (1 + c)`.
+fn reinhard(c: f32) -> f32 {
+    c / (1.0 + c)
+}
+
+/// ACES filmic approximation, matching the operator in `blit.wgsl`.
+fn aces(c: f32) -> f32 {
+    let (a, b, d, e, f) = (2.51f32, 0.03f32, 2.43f32, 0.59f32, 0.14f32);
+    ((c * (a * c + b)) / (c * (d * c + e) + f)).clamp(0.0, 1.0)
+}
+
+/// Write a captured frame to disk. `.exr` keeps the raw HDR floats losslessly;
+/// any other extension is tone-mapped with the same exposure and operator as
+/// the live viewport and written as 8-bit PNG.
+fn save_frame(path: &std::path::Path, frame: &FrameSnapshot, settings: &Settings) {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let result = if ext == "exr" {
+        image::save_buffer(
+            path,
+            bytemuck::cast_slice(frame.pixels.as_slice()),
+            frame.width,
+            frame.height,
+            image::ExtendedColorType::Rgba32F,
+        )
+    } else {
+        let mut bytes = Vec::with_capacity(frame.pixels.len() * 4);
+        for px in &frame.pixels {
+            let c = px.to_array();
+            for channel in c.iter().take(3) {
+                let exposed = settings.exposure * channel;
+                // Mirror the shader: tone-map with the selected operator, then
+                // encode into sRGB space for the 8-bit target.
+                let mapped = if settings.tonemap == 0 {
+                    reinhard(exposed)
+                } else {
+                    aces(exposed)
+                };
+                let srgb = mapped.powf(1.0 / 2.2);
+                bytes.push((srgb.clamp(0.0, 1.0) * 255.0).round() as u8);
+            }
+            bytes.push((c[3].clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+        image::save_buffer_with_format(
+            path,
+            &bytes,
+            frame.width,
+            frame.height,
+            image::ExtendedColorType::Rgba8,
+            image::ImageFormat::Png,
+        )
+    };
+
+    if let Err(err) = result {
+        tracing::error!("failed to save image to {}: {err}", path.display());
+    }
+}
+
+/// Round `value` up to the next multiple of `alignment`.
+fn align_up(value: u32, alignment: u32) -> u32 {
+    value.div_ceil(alignment) * alignment
+}
+
+/// Padded row stride of the staging buffer for a given width, satisfying
+/// wgpu's `COPY_BYTES_PER_ROW_ALIGNMENT`.
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let unpadded = width * std::mem::size_of::<glm::Vec4>() as u32;
+    align_up(unpadded, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+}
+
+/// Most recent frame received by the UI, kept so the File menu can export it.
+#[derive(Clone)]
+struct FrameSnapshot {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
 #[derive(Clone)]
 struct RenderViewCallback {
     receiver: Arc<Receiver<Vec<Color>>>,
+    last_frame: Arc<Mutex<Option<FrameSnapshot>>>,
+    width: u32,
+    height: u32,
+    exposure: f32,
+    tonemap: u32,
 }
 
 impl egui_wgpu::CallbackTrait for RenderViewCallback {
@@ -33,30 +145,63 @@ impl egui_wgpu::CallbackTrait for RenderViewCallback {
         egui_encoder: &mut wgpu::CommandEncoder,
         resources: &mut egui_wgpu::CallbackResources,
     ) -> Vec<wgpu::CommandBuffer> {
-        let resources: &FullScreenTriangleRenderResources = resources.get().unwrap();
+        let resources: &mut FullScreenTriangleRenderResources = resources.get_mut().unwrap();
+
+        // Track the viewport size; reallocate the texture/staging buffer when
+        // the pane is resized so the blit always matches the traced frame.
+        if resources.width != self.width || resources.height != self.height {
+            resources.resize(device, self.width, self.height);
+        }
 
         if let Ok(image) = self.receiver.try_recv() {
             debug!("received frame");
-            queue.write_buffer(
-                &resources.staging_buffer,
-                0,
-                bytemuck::cast_slice(image.as_slice()),
-            );
+            let bytes_per_row = padded_bytes_per_row(resources.width);
+            let row_pixels = resources.width as usize;
+            // Copy row by row so each row lands on its aligned offset in the
+            // padded staging buffer.
+            for y in 0..resources.height as usize {
+                let start = y * row_pixels;
+                let end = start + row_pixels;
+                if end > image.len() {
+                    break;
+                }
+                queue.write_buffer(
+                    &resources.staging_buffer,
+                    y as u64 * bytes_per_row as u64,
+                    bytemuck::cast_slice(&image[start..end]),
+                );
+            }
             egui_encoder.copy_buffer_to_texture(
                 wgpu::ImageCopyBuffer {
                     buffer: &resources.staging_buffer,
                     layout: wgpu::ImageDataLayout {
                         offset: 0,
-                        bytes_per_row: Some((256 * std::mem::size_of::<glm::Vec4>()) as u32),
+                        bytes_per_row: Some(bytes_per_row),
                         rows_per_image: None,
                     },
                 },
                 resources.result_texture.as_image_copy(),
                 resources.result_texture.size(),
             );
+
+            // Keep the latest complete frame around for the File→Save Image path.
+            if image.len() == (resources.width * resources.height) as usize {
+                *self.last_frame.lock().unwrap() = Some(FrameSnapshot {
+                    width: resources.width,
+                    height: resources.height,
+                    pixels: image,
+                });
+            }
         }
 
-        resources.prepare(device, queue); // TODO: pass screen dims here
+        // Push the live tone-mapping controls into the fragment uniform.
+        let uniform = [self.exposure, self.tonemap as f32, 0.0f32, 0.0f32];
+        queue.write_buffer(
+            &resources.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&uniform),
+        );
+
         Vec::new()
     }
 
@@ -74,18 +219,74 @@ impl egui_wgpu::CallbackTrait for RenderViewCallback {
 struct FullScreenTriangleRenderResources {
     pipeline: wgpu::RenderPipeline,
     bind_group: wgpu::BindGroup,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
     staging_buffer: wgpu::Buffer,
     result_texture: wgpu::Texture,
+    width: u32,
+    height: u32,
 }
 
 impl FullScreenTriangleRenderResources {
-    fn prepare(&self, _device: &wgpu::Device, _queue: &wgpu::Queue) {
-        // Update our uniform buffer with the angle from the UI
-        // queue.write_buffer(
-        //     &self.uniform_buffer,
-        //     0,
-        //     bytemuck::cast_slice(&[angle, 0.0, 0.0, 0.0]),
-        // );
+    /// Create a padded staging buffer and result texture for the given size.
+    fn create_target(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+    ) -> (wgpu::Buffer, wgpu::Texture) {
+        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Staging Buffer"),
+            usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            size: padded_bytes_per_row(width) as u64 * height as u64,
+            mapped_at_creation: false,
+        });
+
+        let result_texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            label: Some("Result texture"),
+            view_formats: &[],
+        });
+
+        (staging_buffer, result_texture)
+    }
+
+    /// Reallocate the staging buffer and result texture for a new viewport
+    /// size and rebuild the bind group that points at the new texture.
+    fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let (staging_buffer, result_texture) = Self::create_target(device, width, height);
+        let view = result_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("textures_bind_group"),
+        });
+        self.staging_buffer = staging_buffer;
+        self.result_texture = result_texture;
+        self.width = width;
+        self.height = height;
     }
 
     fn paint(&self, render_pass: &mut wgpu::RenderPass<'_>) {
@@ -106,32 +307,8 @@ impl RenderView {
 
         let device = &wgpu_render_state.device;
 
-        let texture_size = wgpu::Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        };
-
-        let staging_buffer_size: usize =
-            (width * height) as usize * std::mem::size_of::<glm::Vec4>();
-
-        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Staging Buffer"),
-            usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
-            size: staging_buffer_size as u64,
-            mapped_at_creation: false,
-        });
-
-        let result_texture = device.create_texture(&wgpu::TextureDescriptor {
-            size: texture_size,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba32Float,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            label: Some("Result texture"),
-            view_formats: &[],
-        });
+        let (staging_buffer, result_texture) =
+            FullScreenTriangleRenderResources::create_target(device, width, height);
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("blit shader"),
@@ -158,9 +335,26 @@ impl RenderView {
                         ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                         count: None,
                     },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
                 ],
             });
 
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("tonemap uniform"),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            size: (4 * std::mem::size_of::<f32>()) as u64,
+            mapped_at_creation: false,
+        });
+
         let result_texture_view =
             result_texture.create_view(&wgpu::TextureViewDescriptor::default());
         let result_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
@@ -184,6 +378,10 @@ impl RenderView {
                     binding: 1,
                     resource: wgpu::BindingResource::Sampler(&result_sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
             ],
             label: Some("textures_bind_group"),
         });
@@ -241,8 +439,13 @@ impl RenderView {
             .insert(FullScreenTriangleRenderResources {
                 pipeline: render_pipeline,
                 bind_group: textures_bind_group,
+                bind_group_layout: texture_bind_group_layout,
+                sampler: result_sampler,
+                uniform_buffer,
                 staging_buffer,
                 result_texture,
+                width,
+                height,
             });
 
         Some(Self {})
@@ -261,9 +464,16 @@ struct Pane {
     kind: PaneType,
 }
 
-struct TreeBehavior {}
+struct TreeBehavior<'a> {
+    camera: &'a mut CameraController,
+    input_tx: &'a single_value_channel::Updater<Mat4>,
+    resolution_tx: &'a single_value_channel::Updater<(u32, u32)>,
+    last_frame: &'a Arc<Mutex<Option<FrameSnapshot>>>,
+    settings: &'a Arc<Mutex<Settings>>,
+    sample_count: &'a Arc<AtomicU32>,
+}
 
-impl egui_tiles::Behavior<Pane> for TreeBehavior {
+impl egui_tiles::Behavior<Pane> for TreeBehavior<'_> {
     fn tab_title_for_pane(&mut self, pane: &Pane) -> egui::WidgetText {
         format!("Pane {}", pane.nr).into()
     }
@@ -289,11 +499,21 @@ impl egui_tiles::Behavior<Pane> for TreeBehavior {
     ) -> egui_tiles::UiResponse {
         match &pane.kind {
             PaneType::Settings => {
-                // Give each pane a unique color:
-                let color = egui::epaint::Hsva::new(0.103 * pane.nr as f32, 0.5, 0.5, 1.0);
-                ui.painter().rect_filled(ui.max_rect(), 0.0, color);
-
-                ui.label(format!("The contents of pane {}.", pane.nr));
+                let mut settings = self.settings.lock().unwrap();
+                ui.heading("Tone mapping");
+                ui.add(
+                    egui::Slider::new(&mut settings.exposure, 0.0..=8.0).text("Exposure"),
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Operator:");
+                    ui.radio_value(&mut settings.tonemap, 0, "Reinhard");
+                    ui.radio_value(&mut settings.tonemap, 1, "ACES");
+                });
+                ui.separator();
+                ui.label(format!(
+                    "Samples: {}",
+                    self.sample_count.load(Ordering::Relaxed)
+                ));
             }
             PaneType::Render(rx) => {
                 // ui.checkbox(&mut self.checked, "Checked");
@@ -312,20 +532,40 @@ impl egui_tiles::Behavior<Pane> for TreeBehavior {
                         egui::Sense::drag(),
                     );
 
-                    // TODO: pass input to camera controller
-                    if response.has_focus() {
-                        debug!("FOCUS!!!");
-                    }
-
-                    if ui.ctx().input(|i| i.key_pressed(egui::Key::A)) {
-                        debug!("\nPressed");
-                    }
-                    debug!("update!");
+                    // Feed viewport input into the free-fly camera and stream
+                    // the resulting view-projection matrix to the render thread.
+                    let aspect = if rect.height() > 0.0 {
+                        rect.width() / rect.height()
+                    } else {
+                        1.0
+                    };
+                    let dt = ui.ctx().input(|i| i.stable_dt);
+                    let matrix = ui.ctx().input(|i| {
+                        self.camera.update(i, response.drag_delta(), aspect, dt)
+                    });
+                    let _ = self.input_tx.update(matrix);
+
+                    // Track the pane size in physical pixels and ask the render
+                    // thread to match it.
+                    let ppp = ui.ctx().pixels_per_point();
+                    let width_px = (rect.width() * ppp).round().max(1.0) as u32;
+                    let height_px = (rect.height() * ppp).round().max(1.0) as u32;
+                    let _ = self.resolution_tx.update((width_px, height_px));
+
+                    let (exposure, tonemap) = {
+                        let settings = self.settings.lock().unwrap();
+                        (settings.exposure, settings.tonemap)
+                    };
 
                     ui.painter().add(egui_wgpu::Callback::new_paint_callback(
                         rect,
                         RenderViewCallback {
                             receiver: rx.clone(),
+                            last_frame: self.last_frame.clone(),
+                            width: width_px,
+                            height: height_px,
+                            exposure,
+                            tonemap,
                         },
                     ));
                 });
@@ -349,21 +589,38 @@ struct Editor {
     tree: egui_tiles::Tree<Pane>,
     picked_path: Option<String>,
     input_tx: single_value_channel::Updater<Mat4>,
+    resolution_tx: single_value_channel::Updater<(u32, u32)>,
+    last_frame: Arc<Mutex<Option<FrameSnapshot>>>,
+    settings: Arc<Mutex<Settings>>,
+    scene_tx: single_value_channel::Updater<Option<Arc<Scene>>>,
+    sample_count: Arc<AtomicU32>,
+    camera: CameraController,
 }
 
 impl Editor {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         _cc: &eframe::CreationContext<'_>,
         width: u32,
         height: u32,
         rx: Receiver<Vec<Color>>,
         input_tx: single_value_channel::Updater<Mat4>,
+        resolution_tx: single_value_channel::Updater<(u32, u32)>,
+        settings: Arc<Mutex<Settings>>,
+        scene_tx: single_value_channel::Updater<Option<Arc<Scene>>>,
+        sample_count: Arc<AtomicU32>,
     ) -> Self {
         Self {
             viewport: RenderView::new(_cc, width, height),
             tree: create_tree(rx),
             picked_path: None,
             input_tx,
+            resolution_tx,
+            last_frame: Arc::new(Mutex::new(None)),
+            settings,
+            scene_tx,
+            sample_count,
+            camera: CameraController::new(),
         }
     }
 }
@@ -385,12 +642,6 @@ impl eframe::App for Editor {
             ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(!fullscreen));
         }
 
-        if ctx.input_mut(|i: &mut InputState| i.consume_key(egui::Modifiers::NONE, egui::Key::W)) {
-            // TODO: pass input to render thread
-            let new_matrix = glm::perspective(1.0f32, 45.0f32, 0.1f32, 1000.0f32);
-            let _ = self.input_tx.update(new_matrix);
-        }
-
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             // The top panel is often a good place for a menu bar:
             egui::menu::bar(ui, |ui| {
@@ -398,6 +649,30 @@ impl eframe::App for Editor {
                     if ui.button("Open").clicked() {
                         if let Some(path) = rfd::FileDialog::new().pick_file() {
                             self.picked_path = Some(path.display().to_string());
+                            match Scene::load(&path) {
+                                Ok(scene) => {
+                                    let _ = self.scene_tx.update(Some(Arc::new(scene)));
+                                }
+                                Err(err) => {
+                                    tracing::error!(
+                                        "failed to load scene {}: {err}",
+                                        path.display()
+                                    );
+                                }
+                            }
+                        }
+                    }
+
+                    if ui.button("Save Image…").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("OpenEXR (HDR)", &["exr"])
+                            .add_filter("PNG", &["png"])
+                            .save_file()
+                        {
+                            if let Some(frame) = self.last_frame.lock().unwrap().clone() {
+                                let settings = self.settings.lock().unwrap().clone();
+                                save_frame(&path, &frame, &settings);
+                            }
                         }
                     }
 
@@ -416,7 +691,14 @@ impl eframe::App for Editor {
             ui.separator();
         });
         egui::CentralPanel::default().show(ctx, |ui| {
-            let mut behavior = TreeBehavior {};
+            let mut behavior = TreeBehavior {
+                camera: &mut self.camera,
+                input_tx: &self.input_tx,
+                resolution_tx: &self.resolution_tx,
+                last_frame: &self.last_frame,
+                settings: &self.settings,
+                sample_count: &self.sample_count,
+            };
             self.tree.ui(&mut behavior, ui);
         });
 
@@ -459,19 +741,33 @@ fn main() -> Result<(), eframe::Error> {
 
     let (matrix_receiver, matrix_updater) =
         single_value_channel::channel_starting_with(Mat4::identity());
+    let (resolution_receiver, resolution_updater) =
+        single_value_channel::channel_starting_with((256u32, 256u32));
+    let (scene_receiver, scene_updater) =
+        single_value_channel::channel_starting_with(None::<Arc<Scene>>);
     let (render_result_tx, render_result_rx): (Sender<Vec<Color>>, Receiver<Vec<Color>>) =
         crossbeam_channel::unbounded();
 
+    let settings = Arc::new(Mutex::new(Settings::default()));
+    // Published by the render thread, read by the Settings pane to show
+    // convergence progress.
+    let sample_count = Arc::new(AtomicU32::new(0));
+
     let path_tracer_render_lock = Arc::new(RwLock::new(PathTracerRenderContext::new(
         256,
         256,
         render_result_tx.clone(),
         matrix_receiver,
+        resolution_receiver,
+        scene_receiver,
+        settings.clone(),
     )));
     let pt_render = path_tracer_render_lock.clone();
+    let pt_sample_count = sample_count.clone();
     thread::spawn(move || loop {
         if let Ok(mut p) = pt_render.write() {
             render::run_iteration(&mut p);
+            pt_sample_count.store(p.sample_count(), Ordering::Relaxed);
         }
     });
 
@@ -485,6 +781,10 @@ fn main() -> Result<(), eframe::Error> {
                 256,
                 render_result_rx,
                 matrix_updater,
+                resolution_updater,
+                settings,
+                scene_updater,
+                sample_count,
             )))
         }),
     )
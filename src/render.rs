@@ -1,8 +1,10 @@
 use crossbeam_channel::Sender;
+use rayon::prelude::*;
 use tracing::debug;
 
 extern crate nalgebra_glm as glm;
 
+use crate::scene::Scene;
 use crate::Settings;
 use glm::{Mat4, Vec3};
 use log::info;
@@ -19,16 +21,26 @@ impl Color {
             color: [r, g, b, a],
         }
     }
+
+    /// The raw RGBA float components, for export and tone-mapping on the UI side.
+    pub fn to_array(&self) -> [f32; 4] {
+        self.color
+    }
 }
 
 pub struct PathTracerRenderContext {
-    // scene: Arc<EmbreeScene>,
+    scene: Option<Arc<Scene>>,
+    scene_rx: single_value_channel::Receiver<Option<Arc<Scene>>>,
     image_data: Mutex<Vec<Color>>,
+    accum_buffer: Vec<[f32; 4]>,
+    sample_count: u32,
+    last_camera: Mat4,
     result_width: u32,
     result_height: u32,
     view: Mat4,
     tx: Sender<Vec<Color>>,
     input_rx: single_value_channel::Receiver<Mat4>,
+    resolution_rx: single_value_channel::Receiver<(u32, u32)>,
     settings: Arc<Mutex<Settings>>,
 }
 impl PathTracerRenderContext {
@@ -38,44 +50,205 @@ impl PathTracerRenderContext {
         // scene: Arc<EmbreeScene>,
         tx: Sender<Vec<Color>>,
         input_rx: single_value_channel::Receiver<Mat4>,
+        resolution_rx: single_value_channel::Receiver<(u32, u32)>,
+        scene_rx: single_value_channel::Receiver<Option<Arc<Scene>>>,
         settings: Arc<Mutex<Settings>>,
     ) -> Self {
         Self {
             result_height: height,
             result_width: width,
             view: Mat4::new_translation(&Vec3::new(0.0f32, 0.0f32, -1.0f32)),
-            // scene,
+            scene: None,
+            scene_rx,
             image_data: Mutex::new(vec![Color::default(); (width * height) as usize]),
+            accum_buffer: vec![[0.0f32; 4]; (width * height) as usize],
+            sample_count: 0,
+            // Zeroed so the first iteration always reseeds the accumulator.
+            last_camera: Mat4::zeros(),
             tx,
             input_rx,
+            resolution_rx,
             settings,
         }
     }
+
+    /// Number of samples currently averaged in the accumulation buffer.
+    /// The UI reads this to show convergence progress.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+}
+
+/// Epsilon below which two camera matrices are treated as unchanged.
+const CAMERA_EPSILON: f32 = 1e-6;
+
+/// Edge length of a render tile, in pixels.
+const TILE_SIZE: u32 = 32;
+
+/// Trace a single primary ray and return its radiance.
+///
+/// With no scene loaded this falls back to the diagonal stub; once a mesh is
+/// present it casts a camera ray through the pixel and shades hits by their
+/// surface normal. The closure stays free of shared mutable state: the inverse
+/// view-projection, scene and fill color are all taken by shared reference.
+fn trace_pixel(
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    inv_view_proj: &Mat4,
+    scene: Option<&Scene>,
+    bg: &Color,
+) -> Color {
+    let Some(scene) = scene else {
+        return if x == y {
+            *bg
+        } else {
+            Color::new(1.0f32, 1.0f32, 1.0f32, 1.0f32)
+        };
+    };
+
+    // Unproject the pixel center through the near and far planes to build a
+    // world-space primary ray. glm uses the [-1, 1] depth convention.
+    let ndc_x = 2.0 * (x as f32 + 0.5) / width as f32 - 1.0;
+    let ndc_y = 1.0 - 2.0 * (y as f32 + 0.5) / height as f32;
+    let near = unproject(inv_view_proj, ndc_x, ndc_y, -1.0);
+    let far = unproject(inv_view_proj, ndc_x, ndc_y, 1.0);
+    let dir = glm::normalize(&(far - near));
+
+    match scene.intersect(&near, &dir) {
+        Some(hit) => {
+            // Map the normal from [-1, 1] into [0, 1] for a simple debug shade.
+            let shade = 0.5 * (hit.normal + Vec3::new(1.0, 1.0, 1.0));
+            Color::new(shade.x, shade.y, shade.z, 1.0f32)
+        }
+        None => *bg,
+    }
+}
+
+/// Transform a point in normalized device coordinates back into world space.
+fn unproject(inv_view_proj: &Mat4, ndc_x: f32, ndc_y: f32, ndc_z: f32) -> Vec3 {
+    let clip = glm::vec4(ndc_x, ndc_y, ndc_z, 1.0);
+    let world = inv_view_proj * clip;
+    world.xyz() / world.w
 }
 
 pub fn run_iteration(pt_ctx: &mut PathTracerRenderContext) {
-    let camera_matrix = pt_ctx.input_rx.latest();
+    // Reallocate the image and accumulation buffers when the viewport pane
+    // asks for a new resolution. Resizing always restarts accumulation.
+    let (width, height) = *pt_ctx.resolution_rx.latest();
+    if width != pt_ctx.result_width || height != pt_ctx.result_height {
+        pt_ctx.result_width = width;
+        pt_ctx.result_height = height;
+        let pixels = (width * height) as usize;
+        *pt_ctx.image_data.lock().unwrap() = vec![Color::default(); pixels];
+        pt_ctx.accum_buffer = vec![[0.0f32; 4]; pixels];
+        pt_ctx.sample_count = 0;
+    }
+
+    // Swap in a freshly loaded scene when the UI hands us one.
+    let new_scene = pt_ctx.scene_rx.latest().clone();
+    let scene_changed = match (&pt_ctx.scene, &new_scene) {
+        (Some(a), Some(b)) => !Arc::ptr_eq(a, b),
+        (None, None) => false,
+        _ => true,
+    };
+    if scene_changed {
+        pt_ctx.scene = new_scene;
+    }
+
+    let camera_matrix = *pt_ctx.input_rx.latest();
     info!("camera matrix: {}", camera_matrix);
 
-    let settings = pt_ctx.settings.lock().unwrap();
-
-    let bg_color = Color::new(
-        settings.color[0],
-        settings.color[1],
-        settings.color[2],
-        1.0f32,
-    );
-    let mut image_data = pt_ctx.image_data.lock().unwrap().clone();
-    for i in 0..pt_ctx.result_height {
-        for j in 0..pt_ctx.result_width {
-            let mut col = Color::new(1.0f32, 1.0f32, 1.0f32, 1.0f32);
-            if i == j {
-                col = bg_color
+    // Reset the accumulation buffer whenever the camera moves or the scene
+    // changes so the average reflects the current view instead of smearing
+    // radiance across motion.
+    let camera_moved = camera_matrix
+        .iter()
+        .zip(pt_ctx.last_camera.iter())
+        .any(|(a, b)| (a - b).abs() > CAMERA_EPSILON);
+    if camera_moved || scene_changed {
+        for px in pt_ctx.accum_buffer.iter_mut() {
+            *px = [0.0f32; 4];
+        }
+        pt_ctx.sample_count = 0;
+        pt_ctx.last_camera = camera_matrix;
+    }
+
+    let bg_color = {
+        let settings = pt_ctx.settings.lock().unwrap();
+        Color::new(settings.color[0], settings.color[1], settings.color[2], 1.0f32)
+    };
+
+    // Partition the frame into fixed tiles and trace them in parallel. Each
+    // tile produces its own buffer, so the hot path touches no shared mutable
+    // state and needs no locking; the camera matrix and fill color are shared
+    // by value/reference into the per-pixel closure.
+    let width = pt_ctx.result_width;
+    let height = pt_ctx.result_height;
+    let inv_view_proj = glm::inverse(&camera_matrix);
+    let scene = pt_ctx.scene.as_deref();
+    let mut tiles = Vec::new();
+    for ty in (0..height).step_by(TILE_SIZE as usize) {
+        for tx in (0..width).step_by(TILE_SIZE as usize) {
+            let tw = (tx + TILE_SIZE).min(width) - tx;
+            let th = (ty + TILE_SIZE).min(height) - ty;
+            tiles.push((tx, ty, tw, th));
+        }
+    }
+
+    let traced: Vec<((u32, u32, u32, u32), Vec<Color>)> = tiles
+        .par_iter()
+        .map(|&(tx, ty, tw, th)| {
+            let mut local = Vec::with_capacity((tw * th) as usize);
+            for y in ty..ty + th {
+                for x in tx..tx + tw {
+                    local.push(trace_pixel(
+                        x,
+                        y,
+                        width,
+                        height,
+                        &inv_view_proj,
+                        scene,
+                        &bg_color,
+                    ));
+                }
+            }
+            ((tx, ty, tw, th), local)
+        })
+        .collect();
+
+    // Scatter the traced tiles back into the scratch buffer.
+    {
+        let mut image_data = pt_ctx.image_data.lock().unwrap();
+        for ((tx, ty, tw, th), local) in &traced {
+            for ly in 0..*th {
+                for lx in 0..*tw {
+                    let idx = ((ty + ly) * width + (tx + lx)) as usize;
+                    image_data[idx] = local[(ly * tw + lx) as usize];
+                }
+            }
+        }
+    }
+
+    // ...then accumulate it and publish the running average for display.
+    {
+        let image_data = pt_ctx.image_data.lock().unwrap();
+        for (accum, sample) in pt_ctx.accum_buffer.iter_mut().zip(image_data.iter()) {
+            for c in 0..4 {
+                accum[c] += sample.color[c];
             }
-            image_data[(i * pt_ctx.result_width + j) as usize] = col;
         }
     }
-    match pt_ctx.tx.try_send(image_data) {
+    pt_ctx.sample_count += 1;
+
+    let inv = 1.0f32 / pt_ctx.sample_count as f32;
+    let averaged: Vec<Color> = pt_ctx
+        .accum_buffer
+        .iter()
+        .map(|p| Color::new(p[0] * inv, p[1] * inv, p[2] * inv, p[3] * inv))
+        .collect();
+    match pt_ctx.tx.try_send(averaged) {
         Ok(_) => {
             debug!("frame has sent");
         }
@@ -0,0 +1,354 @@
+extern crate nalgebra_glm as glm;
+
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use glm::Vec3;
+
+/// A ray-triangle intersection result in world space.
+pub struct Hit {
+    /// Ray parameter of the hit, used to keep the nearest intersection.
+    pub t: f32,
+    /// Geometric normal of the hit triangle.
+    pub normal: Vec3,
+}
+
+/// A triangle mesh plus an acceleration structure to trace rays against it.
+///
+/// The tracer core only ever talks to this type through [`Scene::intersect`],
+/// so it stays decoupled from the loader and can later gain real materials.
+pub struct Scene {
+    vertices: Vec<Vec3>,
+    indices: Vec<[u32; 3]>,
+    /// One geometric normal per triangle, matching `indices`.
+    normals: Vec<Vec3>,
+    bvh: Bvh,
+}
+
+impl Scene {
+    /// Load a triangle mesh from disk. Only Wavefront OBJ is parsed directly;
+    /// other formats return an `Unsupported` error for now.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        let (vertices, indices) = match ext.as_str() {
+            "obj" => parse_obj(&std::fs::read_to_string(path)?)?,
+            other => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    format!("unsupported mesh format: .{other}"),
+                ));
+            }
+        };
+
+        Ok(Self::from_mesh(vertices, indices))
+    }
+
+    /// Build a scene (including its BVH) from a flat vertex/index buffer.
+    pub fn from_mesh(vertices: Vec<Vec3>, indices: Vec<[u32; 3]>) -> Self {
+        let normals = indices
+            .iter()
+            .map(|tri| {
+                let v0 = vertices[tri[0] as usize];
+                let v1 = vertices[tri[1] as usize];
+                let v2 = vertices[tri[2] as usize];
+                glm::normalize(&glm::cross(&(v1 - v0), &(v2 - v0)))
+            })
+            .collect();
+
+        let bvh = Bvh::build(&vertices, &indices);
+        Self {
+            vertices,
+            indices,
+            normals,
+            bvh,
+        }
+    }
+
+    /// Cast a ray and return the nearest hit, if any.
+    pub fn intersect(&self, origin: &Vec3, dir: &Vec3) -> Option<Hit> {
+        self.bvh.intersect(self, origin, dir)
+    }
+}
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Copy)]
+struct Aabb {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Aabb {
+    fn empty() -> Self {
+        Self {
+            min: Vec3::repeat(f32::INFINITY),
+            max: Vec3::repeat(f32::NEG_INFINITY),
+        }
+    }
+
+    fn expand(&mut self, p: &Vec3) {
+        self.min = min_vec(&self.min, p);
+        self.max = max_vec(&self.max, p);
+    }
+
+    fn merge(&mut self, other: &Aabb) {
+        self.min = min_vec(&self.min, &other.min);
+        self.max = max_vec(&self.max, &other.max);
+    }
+
+    fn centroid(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Slab test; returns true if the ray reaches the box within `tmax`.
+    fn hit(&self, origin: &Vec3, inv_dir: &Vec3, tmax: f32) -> bool {
+        let mut tmin = 0.0f32;
+        let mut tmax = tmax;
+        for axis in 0..3 {
+            let t0 = (self.min[axis] - origin[axis]) * inv_dir[axis];
+            let t1 = (self.max[axis] - origin[axis]) * inv_dir[axis];
+            let (t0, t1) = if inv_dir[axis] < 0.0 { (t1, t0) } else { (t0, t1) };
+            tmin = tmin.max(t0);
+            tmax = tmax.min(t1);
+            if tmax < tmin {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A node in the flattened BVH.
+enum Node {
+    Leaf {
+        bounds: Aabb,
+        start: usize,
+        count: usize,
+    },
+    Internal {
+        bounds: Aabb,
+        left: usize,
+        right: usize,
+    },
+}
+
+/// Median-split bounding volume hierarchy over the mesh triangles.
+struct Bvh {
+    nodes: Vec<Node>,
+    /// Triangle indices reordered so each leaf owns a contiguous range.
+    order: Vec<usize>,
+}
+
+impl Bvh {
+    fn build(vertices: &[Vec3], indices: &[[u32; 3]]) -> Self {
+        let bounds: Vec<Aabb> = indices
+            .iter()
+            .map(|tri| {
+                let mut b = Aabb::empty();
+                for &v in tri {
+                    b.expand(&vertices[v as usize]);
+                }
+                b
+            })
+            .collect();
+
+        let mut order: Vec<usize> = (0..indices.len()).collect();
+        let mut nodes = Vec::new();
+        if !order.is_empty() {
+            build_recursive(&bounds, &mut order, 0, indices.len(), &mut nodes);
+        }
+        Self { nodes, order }
+    }
+
+    fn intersect(&self, scene: &Scene, origin: &Vec3, dir: &Vec3) -> Option<Hit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let inv_dir = Vec3::new(1.0 / dir.x, 1.0 / dir.y, 1.0 / dir.z);
+
+        let mut closest: Option<Hit> = None;
+        let mut stack = vec![0usize];
+        while let Some(node_idx) = stack.pop() {
+            let tmax = closest.as_ref().map(|h| h.t).unwrap_or(f32::INFINITY);
+            match &self.nodes[node_idx] {
+                Node::Leaf {
+                    bounds,
+                    start,
+                    count,
+                } => {
+                    if !bounds.hit(origin, &inv_dir, tmax) {
+                        continue;
+                    }
+                    for &tri in &self.order[*start..*start + *count] {
+                        let idx = scene.indices[tri];
+                        let v0 = scene.vertices[idx[0] as usize];
+                        let v1 = scene.vertices[idx[1] as usize];
+                        let v2 = scene.vertices[idx[2] as usize];
+                        if let Some(t) = intersect_triangle(&v0, &v1, &v2, origin, dir) {
+                            if closest.as_ref().map(|h| t < h.t).unwrap_or(true) {
+                                closest = Some(Hit {
+                                    t,
+                                    normal: scene.normals[tri],
+                                });
+                            }
+                        }
+                    }
+                }
+                Node::Internal {
+                    bounds,
+                    left,
+                    right,
+                } => {
+                    if bounds.hit(origin, &inv_dir, tmax) {
+                        stack.push(*left);
+                        stack.push(*right);
+                    }
+                }
+            }
+        }
+        closest
+    }
+}
+
+/// Maximum triangles kept in a single BVH leaf.
+const LEAF_SIZE: usize = 4;
+
+fn build_recursive(
+    bounds: &[Aabb],
+    order: &mut [usize],
+    start: usize,
+    end: usize,
+    nodes: &mut Vec<Node>,
+) -> usize {
+    let mut node_bounds = Aabb::empty();
+    for &tri in order[start..end].iter() {
+        node_bounds.merge(&bounds[tri]);
+    }
+
+    let count = end - start;
+    let node_idx = nodes.len();
+    if count <= LEAF_SIZE {
+        nodes.push(Node::Leaf {
+            bounds: node_bounds,
+            start,
+            count,
+        });
+        return node_idx;
+    }
+
+    // Split along the widest axis of the centroid bounds.
+    let mut centroid_bounds = Aabb::empty();
+    for &tri in order[start..end].iter() {
+        centroid_bounds.expand(&bounds[tri].centroid());
+    }
+    let extent = centroid_bounds.max - centroid_bounds.min;
+    let axis = if extent.x >= extent.y && extent.x >= extent.z {
+        0
+    } else if extent.y >= extent.z {
+        1
+    } else {
+        2
+    };
+
+    let mid = start + count / 2;
+    order[start..end].select_nth_unstable_by(count / 2, |&a, &b| {
+        bounds[a].centroid()[axis]
+            .partial_cmp(&bounds[b].centroid()[axis])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    // Reserve this node's slot before recursing so child indices stay valid.
+    nodes.push(Node::Leaf {
+        bounds: node_bounds,
+        start,
+        count,
+    });
+    let left = build_recursive(bounds, order, start, mid, nodes);
+    let right = build_recursive(bounds, order, mid, end, nodes);
+    nodes[node_idx] = Node::Internal {
+        bounds: node_bounds,
+        left,
+        right,
+    };
+    node_idx
+}
+
+/// Möller–Trumbore ray-triangle intersection, returning the hit distance.
+fn intersect_triangle(v0: &Vec3, v1: &Vec3, v2: &Vec3, origin: &Vec3, dir: &Vec3) -> Option<f32> {
+    const EPSILON: f32 = 1e-7;
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let h = glm::cross(dir, &edge2);
+    let a = glm::dot(&edge1, &h);
+    if a.abs() < EPSILON {
+        return None;
+    }
+    let f = 1.0 / a;
+    let s = origin - v0;
+    let u = f * glm::dot(&s, &h);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = glm::cross(&s, &edge1);
+    let v = f * glm::dot(dir, &q);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = f * glm::dot(&edge2, &q);
+    if t > EPSILON {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+fn min_vec(a: &Vec3, b: &Vec3) -> Vec3 {
+    Vec3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z))
+}
+
+fn max_vec(a: &Vec3, b: &Vec3) -> Vec3 {
+    Vec3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z))
+}
+
+/// Parse a Wavefront OBJ into a flat vertex/index buffer, triangulating faces
+/// as a fan and ignoring everything but `v` and `f` records.
+fn parse_obj(source: &str) -> Result<(Vec<Vec3>, Vec<[u32; 3]>), Error> {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for line in source.lines() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let coords: Vec<f32> = tokens.filter_map(|t| t.parse().ok()).collect();
+                if coords.len() < 3 {
+                    return Err(Error::new(ErrorKind::InvalidData, "malformed vertex"));
+                }
+                vertices.push(Vec3::new(coords[0], coords[1], coords[2]));
+            }
+            Some("f") => {
+                // Each face vertex may be `v`, `v/vt`, `v/vt/vn` or `v//vn`;
+                // we only need the position index (the first component).
+                let face: Vec<u32> = tokens
+                    .filter_map(|t| t.split('/').next().and_then(|v| v.parse::<u32>().ok()))
+                    .collect();
+                if face.len() < 3 {
+                    return Err(Error::new(ErrorKind::InvalidData, "malformed face"));
+                }
+                for i in 1..face.len() - 1 {
+                    indices.push([face[0] - 1, face[i] - 1, face[i + 1] - 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if vertices.is_empty() || indices.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidData, "empty mesh"));
+    }
+    Ok((vertices, indices))
+}